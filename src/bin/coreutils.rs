@@ -6,17 +6,21 @@
 // file that was distributed with this source code.
 
 use clap::App;
-use clap::Arg;
-use clap::Shell;
 use std::cmp;
 use std::collections::hash_map::HashMap;
-use std::ffi::OsString;
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Dispatch words the binary itself handles before ever consulting the
+/// alias table, so neither a config-file read nor a user alias can shadow
+/// them.
+const RESERVED_COMMANDS: &[&str] = &["completion", "complete", "--list", "--help", "-h"];
+
 include!(concat!(env!("OUT_DIR"), "/uutils_map.rs"));
 
 fn usage<T>(utils: &UtilityMap<T>, name: &str) {
@@ -34,6 +38,148 @@ fn usage<T>(utils: &UtilityMap<T>, name: &str) {
     );
 }
 
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // row buffers are sized by b, so make b the shorter of the two to keep
+    // memory at O(min(m, n)) instead of O(len(b))
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+    let mut curr_row = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr_row[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            curr_row[j] = cmp::min(
+                cmp::min(curr_row[j - 1] + 1, prev_row[j] + 1),
+                prev_row[j - 1] + cost,
+            );
+        }
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+
+    prev_row[b.len()]
+}
+
+// pure so the threshold/sort/cap logic can be unit tested the same way
+// `complete_candidates` is, instead of only being exercised through stdout.
+fn pick_suggestions<'a, T>(utils: &'a UtilityMap<T>, util: &str) -> Vec<&'a str> {
+    let threshold = cmp::max(3, util.len() / 3);
+
+    let mut candidates: Vec<(usize, &str)> = utils
+        .keys()
+        .map(|&name| (levenshtein_distance(util, name), name))
+        .filter(|&(distance, _)| distance <= threshold)
+        .collect();
+    candidates.sort_unstable_by_key(|&(distance, name)| (distance, name));
+
+    candidates.into_iter().take(3).map(|(_, name)| name).collect()
+}
+
+fn print_did_you_mean<T>(utils: &UtilityMap<T>, util: &str) {
+    match &pick_suggestions(utils, util)[..] {
+        [] => {}
+        [only] => println!("Did you mean '{}'?", only),
+        suggestions => println!("Did you mean one of these? {}", suggestions.join(", ")),
+    }
+}
+
+fn load_aliases() -> HashMap<String, String> {
+    merge_aliases(
+        std::env::var_os("HOME").as_deref(),
+        std::env::var("COREUTILS_ALIASES").ok().as_deref(),
+    )
+}
+
+// takes HOME and COREUTILS_ALIASES as parameters so the env-override
+// precedence can be tested without mutating process-wide env vars (racy
+// under cargo test's parallel harness).
+fn merge_aliases(home: Option<&OsStr>, env_aliases: Option<&str>) -> HashMap<String, String> {
+    let mut aliases = HashMap::new();
+
+    if let Some(home) = home {
+        let path = Path::new(home).join(".config/coreutils/aliases.toml");
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            aliases.extend(parse_alias_toml(&contents));
+        }
+    }
+
+    if let Some(env_aliases) = env_aliases {
+        for pair in env_aliases
+            .split(';')
+            .filter(|pair| !pair.trim().is_empty())
+        {
+            if let Some((name, expansion)) = pair.split_once('=') {
+                aliases.insert(name.trim().to_string(), expansion.trim().to_string());
+            }
+        }
+    }
+
+    aliases
+}
+
+fn parse_alias_toml(contents: &str) -> HashMap<String, String> {
+    let mut aliases = HashMap::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().trim_matches(|c| c == '"' || c == '\'');
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            if !key.is_empty() {
+                aliases.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    aliases
+}
+
+fn resolve_alias<T>(
+    name: &str,
+    aliases: &HashMap<String, String>,
+    utils: &UtilityMap<T>,
+) -> Option<Vec<String>> {
+    const MAX_ALIAS_DEPTH: usize = 16;
+
+    if utils.contains_key(name) || RESERVED_COMMANDS.contains(&name) {
+        return None;
+    }
+
+    let mut visited = HashSet::new();
+    visited.insert(name.to_string());
+
+    let mut words: Vec<String> = aliases
+        .get(name)?
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+
+    for _ in 0..MAX_ALIAS_DEPTH {
+        let head = words.first()?.clone();
+        if utils.contains_key(head.as_str()) {
+            return Some(words);
+        }
+        if !visited.insert(head.clone()) {
+            return None; // alias => alias cycle
+        }
+        let mut expanded: Vec<String> = aliases
+            .get(&head)?
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        expanded.extend(words.drain(1..));
+        words = expanded;
+    }
+
+    None
+}
+
 fn binary_path(args: &mut impl Iterator<Item = OsString>) -> PathBuf {
     match args.next() {
         Some(ref s) if !s.is_empty() => PathBuf::from(s),
@@ -49,14 +195,19 @@ fn main() {
     uucore::panic::mute_sigpipe_panic();
 
     let utils = util_map();
-    let mut args = uucore::args_os();
+    // Boxed up front so every `uumain` call site below — including the
+    // alias-splice branch, which can only produce its spliced argument
+    // iterator behind a `Box` — feeds the map the same concrete `T`.
+    let mut args: Box<dyn Iterator<Item = OsString>> = Box::new(uucore::args_os());
 
     let binary = binary_path(&mut args);
     let binary_as_util = name(&binary);
 
     // binary name equals util name?
     if let Some(&(uumain, _)) = utils.get(binary_as_util) {
-        process::exit(uumain((vec![binary.into()].into_iter()).chain(args)));
+        process::exit(uumain(Box::new(
+            (vec![binary.into()].into_iter()).chain(args),
+        )));
     }
 
     // binary name equals prefixed util name?
@@ -76,15 +227,46 @@ fn main() {
 
     // 0th argument equals util name?
     if let Some(util_os) = util_name {
-        let util = util_os.as_os_str().to_string_lossy();
+        let util = util_os.as_os_str().to_string_lossy().into_owned();
+
+        // user-defined alias => splice the alias's preset words ahead of the
+        // remaining arguments and resolve the real util name in its place.
+        // Real utils and reserved commands skip the alias table entirely, so
+        // `complete` in particular never pays for a config-file read.
+        let (util_os, util, mut args): (OsString, String, Box<dyn Iterator<Item = OsString>>) =
+            if utils.contains_key(util.as_str()) || RESERVED_COMMANDS.contains(&util.as_str()) {
+                (util_os, util, args)
+            } else {
+                match resolve_alias(&util, &load_aliases(), &utils) {
+                    Some(mut expansion) => {
+                        let head = expansion.remove(0);
+                        let preset: Vec<OsString> =
+                            expansion.into_iter().map(OsString::from).collect();
+                        (
+                            OsString::from(&head),
+                            head,
+                            Box::new(preset.into_iter().chain(args)),
+                        )
+                    }
+                    None => (util_os, util, args),
+                }
+            };
 
         if util == "completion" {
-            gen_completions(args, utils);
+            print_completion_hook(args);
+        }
+
+        if util == "complete" {
+            run_complete(args, &utils);
+        }
+
+        if util == "--list" {
+            print_util_list(&utils, args);
         }
 
         match utils.get(&util[..]) {
             Some(&(uumain, _)) => {
-                process::exit(uumain((vec![util_os].into_iter()).chain(args)));
+                process::exit(uumain(Box::new((vec![util_os].into_iter()).chain(args))));
             }
             None => {
                 if util == "--help" || util == "-h" {
@@ -94,15 +276,16 @@ fn main() {
 
                         match utils.get(&util[..]) {
                             Some(&(uumain, _)) => {
-                                let code = uumain(
+                                let code = uumain(Box::new(
                                     (vec![util_os, OsString::from("--help")].into_iter())
                                         .chain(args),
-                                );
+                                ));
                                 io::stdout().flush().expect("could not flush stdout");
                                 process::exit(code);
                             }
                             None => {
                                 println!("{}: function/utility not found", util);
+                                print_did_you_mean(&utils, &util);
                                 process::exit(1);
                             }
                         }
@@ -111,6 +294,7 @@ fn main() {
                     process::exit(0);
                 } else {
                     println!("{}: function/utility not found", util);
+                    print_did_you_mean(&utils, &util);
                     process::exit(1);
                 }
             }
@@ -122,49 +306,437 @@ fn main() {
     }
 }
 
-/// Prints completions for the utility in the first parameter for the shell in the second parameter to stdout
-fn gen_completions<T: uucore::Args>(
+fn print_completion_hook(mut args: impl Iterator<Item = OsString>) -> ! {
+    let shell = args.next().unwrap_or_default();
+    let shell = shell.to_string_lossy();
+    let bin_name = std::env::var("PROG_PREFIX").unwrap_or_default() + "coreutils";
+
+    let hook = match &shell[..] {
+        "bash" => bash_completion_hook(&bin_name),
+        "zsh" => format!(
+            "autoload -Uz bashcompinit && bashcompinit\n{}",
+            bash_completion_hook(&bin_name)
+        ),
+        "fish" => format!(
+            "function __{bin}_complete\n    set -l words (commandline -opc) (commandline -ct)\n    {bin} complete --index (math (count $words) - 1) -- $words\nend\ncomplete -c {bin} -f -a '(__{bin}_complete)'",
+            bin = bin_name
+        ),
+        _ => {
+            eprintln!("completion: unsupported shell '{}'", shell);
+            process::exit(1);
+        }
+    };
+
+    println!("{}", hook);
+    process::exit(0);
+}
+
+// reconstructs COMP_WORDS/COMP_CWORD into coreutils complete's
+// `--index N -- word0 word1 ...` protocol. bash's own `-C` only ever
+// appends 3 fixed positional args (command, current word, previous word),
+// so a function is needed to see the full word list.
+fn bash_completion_hook(bin_name: &str) -> String {
+    format!(
+        "__{bin}_complete() {{\n    mapfile -t COMPREPLY < <({bin} complete --index \"$COMP_CWORD\" -- \"${{COMP_WORDS[@]}}\")\n}}\ncomplete -F __{bin}_complete {bin}",
+        bin = bin_name
+    )
+}
+
+fn run_complete<T: uucore::Args>(
     args: impl Iterator<Item = OsString>,
-    util_map: UtilityMap<T>,
+    util_map: &UtilityMap<T>,
 ) -> ! {
-    let all_utilities: Vec<_> = std::iter::once("coreutils")
-        .chain(util_map.keys().copied())
+    let args: Vec<OsString> = args.collect();
+
+    let index = args
+        .iter()
+        .position(|a| a == "--index")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let words: Vec<String> = match args.iter().position(|a| a == "--") {
+        Some(i) => args[i + 1..]
+            .iter()
+            .map(|s| s.to_string_lossy().into_owned())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    for candidate in complete_candidates(util_map, &words, index) {
+        println!("{}", candidate);
+    }
+    process::exit(0);
+}
+
+fn complete_candidates<T: uucore::Args>(
+    util_map: &UtilityMap<T>,
+    words: &[String],
+    index: usize,
+) -> Vec<String> {
+    let current = words.get(index).map(String::as_str).unwrap_or("");
+
+    if index <= 1 {
+        let mut candidates: Vec<String> = util_map
+            .keys()
+            .filter(|util| util.starts_with(current))
+            .map(|&util| util.to_string())
+            .collect();
+        candidates.sort_unstable();
+        return candidates;
+    }
+
+    let util_name = match words.get(1) {
+        Some(name) => name.as_str(),
+        None => return Vec::new(),
+    };
+
+    let sub_app = match util_map.get(util_name) {
+        Some(&(_, sub_app)) => sub_app(),
+        None => return Vec::new(),
+    };
+
+    if current.starts_with('-') {
+        flag_candidates(&sub_app, current)
+    } else {
+        let mut candidates = subcommand_candidates(&sub_app, current);
+        candidates.extend(path_candidates(current));
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
+// reads the App's own flag/opt tables directly instead of text-scraping
+// `--help` output, so a long about/help string that wraps (or that itself
+// starts a line with `-`) can't corrupt the candidate list.
+fn flag_candidates(app: &App, current: &str) -> Vec<String> {
+    let mut candidates: Vec<String> = Vec::new();
+
+    for flag in &app.p.flags {
+        if let Some(long) = flag.s.long {
+            candidates.push(format!("--{}", long));
+        }
+        if let Some(short) = flag.s.short {
+            candidates.push(format!("-{}", short));
+        }
+    }
+    for opt in &app.p.opts {
+        if let Some(long) = opt.s.long {
+            candidates.push(format!("--{}", long));
+        }
+        if let Some(short) = opt.s.short {
+            candidates.push(format!("-{}", short));
+        }
+    }
+
+    candidates.retain(|candidate| candidate.starts_with(current));
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+fn subcommand_candidates(app: &App, current: &str) -> Vec<String> {
+    let mut candidates: Vec<String> = app
+        .p
+        .subcommands
+        .iter()
+        .map(|sub| sub.p.meta.name.clone())
+        .filter(|name| name.starts_with(current))
         .collect();
+    candidates.sort_unstable();
+    candidates
+}
 
-    let matches = App::new("completion")
-        .about("Prints completions to stdout")
-        .arg(
-            Arg::with_name("utility")
-                .possible_values(&all_utilities)
-                .required(true),
-        )
-        .arg(
-            Arg::with_name("shell")
-                .possible_values(&Shell::variants())
-                .required(true),
-        )
-        .get_matches_from(std::iter::once(OsString::from("completion")).chain(args));
-
-    let utility = matches.value_of("utility").unwrap();
-    let shell = matches.value_of("shell").unwrap();
-
-    let mut app = if utility == "coreutils" {
-        gen_coreutils_app(util_map)
+fn path_candidates(current: &str) -> Vec<String> {
+    let (dir, prefix) = match current.rfind('/') {
+        Some(i) => (&current[..=i], &current[i + 1..]),
+        None => ("", current),
+    };
+    let dir_path = if dir.is_empty() {
+        Path::new(".")
     } else {
-        util_map.get(utility).unwrap().1()
+        Path::new(dir)
+    };
+
+    let mut candidates: Vec<String> = match std::fs::read_dir(dir_path) {
+        Ok(entries) => entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                if !file_name.starts_with(prefix) {
+                    return None;
+                }
+                let mut candidate = format!("{}{}", dir, file_name);
+                if entry.path().is_dir() {
+                    candidate.push('/');
+                }
+                Some(candidate)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
     };
-    let shell: Shell = shell.parse().unwrap();
-    let bin_name = std::env::var("PROG_PREFIX").unwrap_or_default() + utility;
+    candidates.sort_unstable();
+    candidates
+}
+
+fn print_util_list<T: uucore::Args>(
+    utils: &UtilityMap<T>,
+    mut args: impl Iterator<Item = OsString>,
+) -> ! {
+    let json = args.any(|arg| arg == "--json");
+
+    let mut names: Vec<&str> = utils.keys().copied().collect();
+    names.sort_unstable();
+
+    if json {
+        let entries: Vec<String> = names
+            .iter()
+            .map(|&name| {
+                let (_, sub_app) = utils.get(name).unwrap();
+                let about = util_about(&sub_app());
+                format!(
+                    "{{\"name\":{},\"about\":{}}}",
+                    json_escape(name),
+                    json_escape(&about)
+                )
+            })
+            .collect();
+        println!("[{}]", entries.join(","));
+    } else {
+        for name in names {
+            println!("{}", name);
+        }
+    }
 
-    app.gen_completions_to(bin_name, shell, &mut io::stdout());
-    io::stdout().flush().unwrap();
     process::exit(0);
 }
 
-fn gen_coreutils_app<T: uucore::Args>(util_map: UtilityMap<T>) -> App<'static, 'static> {
-    let mut app = App::new("coreutils");
-    for (_, (_, sub_app)) in util_map {
-        app = app.subcommand(sub_app());
+// pulled straight off the App's own about field rather than scraping
+// `--help` output, so a description that wraps (or contains a blank line)
+// can't get mangled.
+fn util_about(app: &App) -> String {
+    app.p.meta.about.unwrap_or_default().to_string()
+}
+
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    type TestArgs = std::vec::IntoIter<OsString>;
+
+    fn noop_main(_args: TestArgs) -> i32 {
+        0
+    }
+
+    fn ls_app() -> App<'static, 'static> {
+        App::new("ls")
+            .about("list directory contents")
+            .arg(Arg::with_name("all").short("a").long("all"))
+            .arg(Arg::with_name("long").short("l"))
+            .arg(
+                Arg::with_name("color")
+                    .long("color")
+                    .min_values(0)
+                    .value_name("WHEN"),
+            )
+    }
+
+    fn cat_app() -> App<'static, 'static> {
+        App::new("cat").about("concatenate files")
+    }
+
+    fn test_util_map() -> UtilityMap<TestArgs> {
+        let mut map: UtilityMap<TestArgs> = HashMap::new();
+        map.insert("ls", (noop_main as fn(TestArgs) -> i32, ls_app));
+        map.insert("cat", (noop_main as fn(TestArgs) -> i32, cat_app));
+        map
+    }
+
+    #[test]
+    fn levenshtein_distance_identical_strings_is_zero() {
+        assert_eq!(levenshtein_distance("ls", "ls"), 0);
+    }
+
+    #[test]
+    fn levenshtein_distance_matches_known_value() {
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn pick_suggestions_sorts_by_distance_and_caps_at_three() {
+        let mut map: UtilityMap<TestArgs> = HashMap::new();
+        for name in ["ls", "lss", "lsw", "lsx", "lsz"] {
+            map.insert(name, (noop_main as fn(TestArgs) -> i32, cat_app));
+        }
+
+        assert_eq!(pick_suggestions(&map, "lss"), vec!["lss", "ls", "lsw"]);
+    }
+
+    #[test]
+    fn pick_suggestions_drops_names_past_the_distance_threshold() {
+        let utils = test_util_map();
+        assert!(pick_suggestions(&utils, "zzzzzzzzzz").is_empty());
+    }
+
+    #[test]
+    fn util_about_extracts_the_about_line() {
+        assert_eq!(util_about(&ls_app()), "list directory contents");
+    }
+
+    #[test]
+    fn json_escape_escapes_quotes_and_backslashes() {
+        assert_eq!(json_escape("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    }
+
+    #[test]
+    fn resolve_alias_expands_to_real_util_with_preset_args() {
+        let utils = test_util_map();
+        let mut aliases = HashMap::new();
+        aliases.insert("ll".to_string(), "ls -l".to_string());
+
+        assert_eq!(
+            resolve_alias("ll", &aliases, &utils),
+            Some(vec!["ls".to_string(), "-l".to_string()])
+        );
+    }
+
+    #[test]
+    fn resolve_alias_follows_alias_to_alias_chains() {
+        let utils = test_util_map();
+        let mut aliases = HashMap::new();
+        aliases.insert("ll".to_string(), "ls -l".to_string());
+        aliases.insert("dir".to_string(), "ll".to_string());
+
+        assert_eq!(
+            resolve_alias("dir", &aliases, &utils),
+            Some(vec!["ls".to_string(), "-l".to_string()])
+        );
+    }
+
+    #[test]
+    fn resolve_alias_detects_alias_to_alias_cycles() {
+        let utils = test_util_map();
+        let mut aliases = HashMap::new();
+        aliases.insert("a".to_string(), "b".to_string());
+        aliases.insert("b".to_string(), "a".to_string());
+
+        assert_eq!(resolve_alias("a", &aliases, &utils), None);
+    }
+
+    #[test]
+    fn resolve_alias_never_shadows_a_real_util() {
+        let utils = test_util_map();
+        let mut aliases = HashMap::new();
+        aliases.insert("ls".to_string(), "cat".to_string());
+
+        assert_eq!(resolve_alias("ls", &aliases, &utils), None);
+    }
+
+    #[test]
+    fn resolve_alias_never_shadows_a_reserved_command() {
+        let utils = test_util_map();
+        let mut aliases = HashMap::new();
+        aliases.insert("--list".to_string(), "ls -la".to_string());
+
+        assert_eq!(resolve_alias("--list", &aliases, &utils), None);
+    }
+
+    #[test]
+    fn parse_alias_toml_parses_quoted_values_and_skips_comments() {
+        let toml = "# comment\nll = \"ls -l\"\n\nmd5 = \"hashsum --md5\"\n";
+        let aliases = parse_alias_toml(toml);
+
+        assert_eq!(aliases.get("ll"), Some(&"ls -l".to_string()));
+        assert_eq!(aliases.get("md5"), Some(&"hashsum --md5".to_string()));
+    }
+
+    #[test]
+    fn merge_aliases_env_var_overrides_config_file() {
+        // a throwaway dir standing in for $HOME, not the real thing, so this
+        // test doesn't need to touch process-wide env vars at all
+        let home = std::env::temp_dir().join(format!(
+            "coreutils-aliases-test-{}",
+            std::process::id()
+        ));
+        let config_dir = home.join(".config/coreutils");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("aliases.toml"), "ll = \"ls -la\"\n").unwrap();
+
+        let aliases = merge_aliases(
+            Some(home.as_os_str()),
+            Some("ll=ls -l;md5=hashsum --md5"),
+        );
+
+        std::fs::remove_dir_all(&home).unwrap();
+
+        // env var wins over the conflicting "ll" entry from the config file
+        assert_eq!(aliases.get("ll"), Some(&"ls -l".to_string()));
+        assert_eq!(aliases.get("md5"), Some(&"hashsum --md5".to_string()));
+    }
+
+    #[test]
+    fn complete_candidates_filters_util_names_by_prefix() {
+        let utils = test_util_map();
+        let words = vec!["coreutils".to_string(), "l".to_string()];
+        assert_eq!(
+            complete_candidates(&utils, &words, 1),
+            vec!["ls".to_string()]
+        );
+    }
+
+    #[test]
+    fn complete_candidates_returns_nothing_for_an_unknown_util() {
+        let utils = test_util_map();
+        let words = vec!["coreutils".to_string(), "nope".to_string(), "-".to_string()];
+        assert!(complete_candidates(&utils, &words, 2).is_empty());
+    }
+
+    #[test]
+    fn flag_candidates_filters_by_prefix() {
+        let app = ls_app();
+        assert_eq!(flag_candidates(&app, "--a"), vec!["--all".to_string()]);
+    }
+
+    #[test]
+    fn flag_candidates_ignores_value_name() {
+        let app = ls_app();
+        assert_eq!(flag_candidates(&app, "--col"), vec!["--color".to_string()]);
+    }
+
+    #[test]
+    fn path_candidates_filters_dir_entries_by_prefix() {
+        let dir =
+            std::env::temp_dir().join(format!("coreutils-complete-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("foo.txt"), b"").unwrap();
+        std::fs::write(dir.join("bar.txt"), b"").unwrap();
+
+        let prefix = dir.join("fo").to_string_lossy().into_owned();
+        let candidates = path_candidates(&prefix);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(candidates.len(), 1);
+        assert!(candidates[0].ends_with("foo.txt"));
     }
-    app
 }